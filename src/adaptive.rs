@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+//FGK自适应huffman树: 不需要预先统计频数，边读边编码/解码，树随着符号的到来动态演化
+struct Node {
+    weight: usize,
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+    symbol: Option<u8>,
+    is_nyt: bool,
+    //节点编号: 编号越大离NYT(固定为0)越"远"，同权值节点中编号最大者即兄弟性质维护时的替换首选
+    number: usize,
+}
+
+//NYT(not-yet-transmitted)节点代表"尚未出现过的符号"，编解码双方各自维护一棵结构完全相同的树
+pub(crate) struct AdaptiveModel {
+    nodes: Vec<Node>,
+    root: usize,
+    nyt: usize,
+    leaves: HashMap<u8, usize>,
+}
+
+impl AdaptiveModel {
+    pub(crate) fn new() -> Self {
+        AdaptiveModel {
+            nodes: vec![Node {
+                weight: 0,
+                parent: None,
+                left: None,
+                right: None,
+                symbol: None,
+                is_nyt: true,
+                number: 0,
+            }],
+            root: 0,
+            nyt: 0,
+            leaves: HashMap::new(),
+        }
+    }
+
+    //编码一个符号: 已出现过的符号发送其当前编码，新符号发送NYT编码后紧跟8位原始字节，然后更新树
+    pub(crate) fn encode(&mut self, ch: u8) -> Vec<u8> {
+        let bits = if let Some(&leaf) = self.leaves.get(&ch) {
+            self.path_to(leaf)
+        } else {
+            let mut bits = self.path_to(self.nyt);
+            for i in (0..8).rev() {
+                bits.push((ch >> i) & 1);
+            }
+            bits
+        };
+        self.update(ch);
+        bits
+    }
+
+    //解码一个符号: 从根按位走向叶子，遇到NYT则再读8位作为原始字节，然后用相同规则更新树
+    pub(crate) fn decode(&mut self, bits: &mut impl Iterator<Item = u8>) -> Option<u8> {
+        let mut idx = self.root;
+        loop {
+            if self.nodes[idx].is_nyt {
+                let mut ch = 0u8;
+                for _ in 0..8 {
+                    ch = (ch << 1) | bits.next()?;
+                }
+                self.update(ch);
+                return Some(ch);
+            }
+            if let Some(ch) = self.nodes[idx].symbol {
+                self.update(ch);
+                return Some(ch);
+            }
+            idx = match bits.next()? {
+                0 => self.nodes[idx].left.unwrap(),
+                _ => self.nodes[idx].right.unwrap(),
+            };
+        }
+    }
+
+    //从根到node的路径(左0右1)
+    fn path_to(&self, mut idx: usize) -> Vec<u8> {
+        let mut path = Vec::new();
+        while let Some(p) = self.nodes[idx].parent {
+            path.push(if self.nodes[p].left == Some(idx) { 0 } else { 1 });
+            idx = p;
+        }
+        path.reverse();
+        path
+    }
+
+    //将符号ch的权值加一并沿途维护兄弟性质; 若ch是新符号，先把NYT叶子分裂成新内部节点+新NYT+新叶子
+    fn update(&mut self, ch: u8) {
+        let mut node = match self.leaves.get(&ch) {
+            Some(&leaf) => leaf,
+            None => self.split_nyt(ch),
+        };
+        loop {
+            if let Some(swap_with) = self.find_swap_candidate(node) {
+                self.swap_nodes(node, swap_with);
+            }
+            self.nodes[node].weight += 1;
+            match self.nodes[node].parent {
+                Some(p) => node = p,
+                None => break,
+            }
+        }
+    }
+
+    fn split_nyt(&mut self, ch: u8) -> usize {
+        let old_nyt = self.nyt;
+        let parent = self.nodes[old_nyt].parent;
+        //NYT编号固定为0，是全局最小编号；分裂出两个新叶子和一个新内部节点占用0,1,2号，
+        //其余所有存活节点的编号整体后移2位腾出空间
+        let old_number = self.nodes[old_nyt].number;
+        for (i, node) in self.nodes.iter_mut().enumerate() {
+            if i != old_nyt {
+                node.number += 2;
+            }
+        }
+
+        let internal = self.nodes.len();
+        self.nodes.push(Node {
+            weight: 0,
+            parent,
+            left: None,
+            right: None,
+            symbol: None,
+            is_nyt: false,
+            number: old_number + 2,
+        });
+        let new_nyt = self.nodes.len();
+        self.nodes.push(Node {
+            weight: 0,
+            parent: Some(internal),
+            left: None,
+            right: None,
+            symbol: None,
+            is_nyt: true,
+            number: old_number,
+        });
+        let new_leaf = self.nodes.len();
+        self.nodes.push(Node {
+            weight: 0,
+            parent: Some(internal),
+            left: None,
+            right: None,
+            symbol: Some(ch),
+            is_nyt: false,
+            number: old_number + 1,
+        });
+        self.nodes[internal].left = Some(new_nyt);
+        self.nodes[internal].right = Some(new_leaf);
+
+        match parent {
+            Some(p) if self.nodes[p].left == Some(old_nyt) => self.nodes[p].left = Some(internal),
+            Some(p) => self.nodes[p].right = Some(internal),
+            None => self.root = internal,
+        }
+
+        self.nyt = new_nyt;
+        self.leaves.insert(ch, new_leaf);
+        new_leaf
+    }
+
+    //收集当前树中可达的(存活)节点; split_nyt中被替换下来的旧节点仍留在nodes数组里但已脱离树，
+    //不应参与编号比较，因此只通过树形结构遍历而不是直接扫描nodes数组
+    fn live_nodes(&self) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.collect(self.root, &mut out);
+        out
+    }
+    fn collect(&self, idx: usize, out: &mut Vec<usize>) {
+        out.push(idx);
+        if let Some(l) = self.nodes[idx].left {
+            self.collect(l, out);
+        }
+        if let Some(r) = self.nodes[idx].right {
+            self.collect(r, out);
+        }
+    }
+
+    //与node权值相同、编号最高的节点，即该权值块的"块首"，排除node自身、node的祖先/后代
+    //(否则交换会破坏树结构)以及永远固定在最低编号的NYT节点；若node自己已经是块首(没有
+    //编号比它更高的同权值节点)，则不需要交换
+    fn find_swap_candidate(&self, node: usize) -> Option<usize> {
+        let weight = self.nodes[node].weight;
+        let node_number = self.nodes[node].number;
+        let mut best: Option<usize> = None;
+        for cand in self.live_nodes() {
+            if cand == node || cand == self.nyt || self.nodes[cand].weight != weight {
+                continue;
+            }
+            if self.nodes[cand].number <= node_number {
+                continue;
+            }
+            if self.is_ancestor(cand, node) || self.is_ancestor(node, cand) {
+                continue;
+            }
+            if best.is_none_or(|b| self.nodes[cand].number > self.nodes[b].number) {
+                best = Some(cand);
+            }
+        }
+        best
+    }
+
+    //maybe_ancestor是否为node的祖先节点
+    fn is_ancestor(&self, maybe_ancestor: usize, node: usize) -> bool {
+        let mut cur = self.nodes[node].parent;
+        while let Some(p) = cur {
+            if p == maybe_ancestor {
+                return true;
+            }
+            cur = self.nodes[p].parent;
+        }
+        false
+    }
+
+    //交换a、b在树中的位置(父节点的子指针)及各自的编号，但保留各自原有的子树
+    fn swap_nodes(&mut self, a: usize, b: usize) {
+        let na = self.nodes[a].number;
+        let nb = self.nodes[b].number;
+        self.nodes[a].number = nb;
+        self.nodes[b].number = na;
+
+        let pa = self.nodes[a].parent;
+        let pb = self.nodes[b].parent;
+        if pa == pb {
+            if let Some(p) = pa {
+                if self.nodes[p].left == Some(a) {
+                    self.nodes[p].left = Some(b);
+                    self.nodes[p].right = Some(a);
+                } else {
+                    self.nodes[p].left = Some(a);
+                    self.nodes[p].right = Some(b);
+                }
+            }
+            return;
+        }
+        match pa {
+            Some(p) if self.nodes[p].left == Some(a) => self.nodes[p].left = Some(b),
+            Some(p) => self.nodes[p].right = Some(b),
+            None => self.root = b,
+        }
+        match pb {
+            Some(p) if self.nodes[p].left == Some(b) => self.nodes[p].left = Some(a),
+            Some(p) => self.nodes[p].right = Some(a),
+            None => self.root = a,
+        }
+        self.nodes[a].parent = pb;
+        self.nodes[b].parent = pa;
+    }
+}
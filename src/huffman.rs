@@ -0,0 +1,282 @@
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+//压缩文件的第一个字节标记头部格式，供解码时自动识别，无需额外传参
+pub(crate) const FORMAT_TREE: u8 = 0;
+pub(crate) const FORMAT_CANONICAL: u8 = 1;
+pub(crate) const FORMAT_ADAPTIVE: u8 = 2;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Inner {
+    pub(crate) ch: u8,
+    pub(crate) weight: usize,
+}
+impl Inner {
+    pub(crate) fn new(ch: u8, weight: usize) -> Self {
+        Inner { ch, weight }
+    }
+}
+impl std::ops::Add for Inner {
+    type Output = Self;
+    //非字符节点，ch为0
+    fn add(self, other: Self) -> Self {
+        Inner {
+            ch: 0,
+            weight: self.weight + other.weight,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct HuffmanTree {
+    pub(crate) inner: Inner,
+    pub(crate) left_child: Option<Box<HuffmanTree>>,
+    pub(crate) right_child: Option<Box<HuffmanTree>>,
+}
+
+impl HuffmanTree {
+    pub(crate) fn new(inner: Inner) -> Self {
+        HuffmanTree {
+            inner,
+            left_child: None,
+            right_child: None,
+        }
+    }
+    pub(crate) fn is_leaf(&self) -> bool {
+        self.left_child.is_none() && self.right_child.is_none()
+    }
+    pub(crate) fn merge_hufftree(tree1: Box<HuffmanTree>, tree2: Box<HuffmanTree>) -> Box<HuffmanTree> {
+        let merged_node = HuffmanTree {
+            inner: tree1.inner + tree2.inner,
+            left_child: Some(tree1),
+            right_child: Some(tree2),
+        };
+        Box::new(merged_node)
+    }
+}
+
+//BinaryHeap是大顶堆，用该包装类型反转weight的比较顺序，使堆顶始终是权值最小的树
+struct MinTree(Box<HuffmanTree>);
+impl PartialEq for MinTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.inner.weight == other.0.inner.weight
+    }
+}
+impl Eq for MinTree {}
+impl PartialOrd for MinTree {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for MinTree {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.inner.weight.cmp(&self.0.inner.weight)
+    }
+}
+
+//由字符频数表建立huffman tree，使用最小堆代替每次合并都重新排序的森林，复杂度O(n log n)
+pub(crate) fn create_huffman_tree(freq: HashMap<u8, usize>) -> Box<HuffmanTree> {
+    let mut heap: BinaryHeap<MinTree> = BinaryHeap::new();
+    for (ch, weight) in freq.into_iter() {
+        heap.push(MinTree(Box::new(HuffmanTree::new(Inner::new(ch, weight)))));
+    }
+    //插入空树, (解决只有一种字符或空文件)
+    heap.push(MinTree(Box::new(HuffmanTree::new(Inner::new(0, 0)))));
+    //不断取出最小权重的两棵树，合并后放回堆中，直至只剩一棵树
+    while heap.len() > 1 {
+        let tree1 = heap.pop().unwrap().0;
+        let tree2 = heap.pop().unwrap().0;
+        heap.push(MinTree(HuffmanTree::merge_hufftree(tree1, tree2)));
+    }
+    heap.pop().unwrap().0
+}
+
+pub(crate) fn freq_table(buff: &[u8]) -> HashMap<u8, usize> {
+    let mut freq: HashMap<u8, usize> = HashMap::new();
+    for ch in buff {
+        //使用hashtable 记录字符出现频数
+        if freq.contains_key(ch) {
+            *freq.get_mut(ch).unwrap() += 1;
+        } else {
+            freq.insert(*ch, 1);
+        }
+    }
+    freq
+}
+
+//递归获取编码， code 是到达当前节点时的路径码（左0，右1),
+//叶子节点由是否有子节点判断，而非ch是否为0，这样字符0x00也能被正确编码
+pub(crate) fn generate_huffman_code(
+    mut code: Vec<u8>,
+    key_code: &mut HashMap<u8, Vec<u8>>,
+    mut root: HuffmanTree,
+) {
+    if root.is_leaf() {
+        key_code.insert(root.inner.ch, code);
+        return;
+    }
+    if root.left_child.is_some() {
+        code.push(0);
+        generate_huffman_code(code.clone(), key_code, *root.left_child.take().unwrap());
+    }
+    if root.right_child.is_some() {
+        code.pop();
+        code.push(1);
+        generate_huffman_code(code.clone(), key_code, *root.right_child.take().unwrap());
+    }
+}
+
+//与generate_huffman_code相同的遍历顺序，但记录 code->字符 以供解码使用
+pub(crate) fn generate_decode_table(
+    mut code: Vec<u8>,
+    key_code: &mut HashMap<Vec<u8>, u8>,
+    mut root: HuffmanTree,
+) {
+    if root.is_leaf() {
+        key_code.insert(code, root.inner.ch);
+        return;
+    }
+    if root.left_child.is_some() {
+        code.push(0);
+        generate_decode_table(code.clone(), key_code, *root.left_child.take().unwrap());
+    }
+    if root.right_child.is_some() {
+        code.pop();
+        code.push(1);
+        generate_decode_table(code.clone(), key_code, *root.right_child.take().unwrap());
+    }
+}
+
+//先序遍历序列化huffman树: 内部节点写入一位`1`, 叶子节点写入一位`0`后紧跟8位原始字符
+//这样压缩文件自身即可还原出树结构，不再需要额外保存频数表
+pub(crate) fn serialize_tree(tree: &HuffmanTree, bits: &mut Vec<u8>) {
+    if tree.is_leaf() {
+        bits.push(0);
+        for i in (0..8).rev() {
+            bits.push((tree.inner.ch >> i) & 1);
+        }
+    } else {
+        bits.push(1);
+        serialize_tree(tree.left_child.as_ref().unwrap(), bits);
+        serialize_tree(tree.right_child.as_ref().unwrap(), bits);
+    }
+}
+
+//serialize_tree的逆过程，从bits[*pos..]递归读回huffman树，pos被更新到树结构结束的位置
+//输入若被截断或损坏，返回Err而不是索引越界panic
+pub(crate) fn deserialize_tree(bits: &[u8], pos: &mut usize) -> Result<Box<HuffmanTree>> {
+    let tag = *bits
+        .get(*pos)
+        .ok_or_else(|| anyhow!("tree data is truncated"))?;
+    *pos += 1;
+    if tag == 0 {
+        if *pos + 8 > bits.len() {
+            return Err(anyhow!("tree data is truncated"));
+        }
+        let mut ch: u8 = 0;
+        for _ in 0..8 {
+            ch = (ch << 1) | bits[*pos];
+            *pos += 1;
+        }
+        Ok(Box::new(HuffmanTree::new(Inner::new(ch, 0))))
+    } else {
+        let left = deserialize_tree(bits, pos)?;
+        let right = deserialize_tree(bits, pos)?;
+        Ok(Box::new(HuffmanTree {
+            inner: Inner::new(0, 0),
+            left_child: Some(left),
+            right_child: Some(right),
+        }))
+    }
+}
+
+//由(符号, 码长)列表生成规范huffman编码: 先按(码长,符号值)排序，最短码长的第一个符号编码为全0，
+//此后每个符号在前一个编码基础上加1，若码长变长则在加1后再左移(左移的0位相当于在编码末尾补0)
+//code直接用位向量表示而非定宽整数，码长没有上限，不会像固定宽度整数那样在码长较长时移位溢出
+pub(crate) fn generate_canonical_codes(lengths: &[(u8, u16)]) -> HashMap<u8, Vec<u8>> {
+    let mut sorted = lengths.to_vec();
+    sorted.sort_by_key(|(ch, len)| (*len, *ch));
+
+    let mut codes = HashMap::new();
+    let mut code: Vec<u8> = Vec::new();
+    let mut prev_len = 0u16;
+    for (ch, len) in sorted {
+        code.resize(code.len() + (len - prev_len) as usize, 0);
+        codes.insert(ch, code.clone());
+        increment(&mut code);
+        prev_len = len;
+    }
+    codes
+}
+
+//二进制加一，从末位(最低位)开始向前进位
+fn increment(code: &mut [u8]) {
+    for bit in code.iter_mut().rev() {
+        if *bit == 0 {
+            *bit = 1;
+            return;
+        }
+        *bit = 0;
+    }
+}
+
+//将源文件的字符转变为编码串
+pub(crate) fn code_original_file(buff: &[u8], codes: &HashMap<u8, Vec<u8>>) -> Vec<u8> {
+    buff.iter()
+        .flat_map(|ch| codes.get(ch).unwrap().clone())
+        .collect()
+}
+
+//将位序列打包为字节流，最后一个字节不足8位时用0补齐
+pub(crate) fn pack_bits(bits: &[u8]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|byte| {
+            let mut result: u8 = 0;
+            for (i, bit) in byte.iter().enumerate() {
+                result += bit * (2u8.pow(7 - i as u32));
+            }
+            result
+        })
+        .collect::<Vec<u8>>()
+}
+
+//单个十进制树转二进制数组
+pub(crate) fn d2b(d: u8) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut t = 128;
+    for _i in 0..8 {
+        if d & t != 0 {
+            result.push(1);
+        } else {
+            result.push(0);
+        }
+        t >>= 1;
+    }
+    result
+}
+
+//original_len 为原始文件的字节数，达到该数量后立即停止，避免打包产生的末尾填充位被误译为符号；
+//若数据在凑够original_len个字节前就用完，说明输入被截断或损坏，返回Err而不是默默产出过短的结果
+pub(crate) fn decode(codes: HashMap<Vec<u8>, u8>, buff: &[u8], original_len: usize) -> Result<Vec<u8>> {
+    let mut window = Vec::new();
+    let mut result = Vec::new();
+    for b in buff {
+        if result.len() == original_len {
+            break;
+        }
+        window.push(*b);
+        if let Some(ch) = codes.get(&window) {
+            result.push(*ch);
+            window.clear();
+        }
+    }
+    if result.len() != original_len {
+        return Err(anyhow!(
+            "compressed data is truncated: expected {} decoded bytes, got {}",
+            original_len,
+            result.len()
+        ));
+    }
+    Ok(result)
+}
@@ -0,0 +1,205 @@
+//! 将字符流用huffman编码压缩/解压，核心逻辑与CLI解耦，可作为库单独使用
+
+mod adaptive;
+mod huffman;
+
+use adaptive::AdaptiveModel;
+use anyhow::{anyhow, Result};
+use huffman::{FORMAT_ADAPTIVE, FORMAT_CANONICAL, FORMAT_TREE};
+use std::collections::HashMap;
+
+//所有compress_*函数的输出均以 [格式标记: 1字节][原始字节数: u64 LE] 开头，decompress据此自描述解析
+
+//以先序序列化的huffman树作为头部，生成自包含的压缩数据
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let freq = huffman::freq_table(input);
+    let tree = huffman::create_huffman_tree(freq);
+    let mut codes = HashMap::new();
+    huffman::generate_huffman_code(Vec::new(), &mut codes, (*tree).clone());
+
+    let mut bits = Vec::new();
+    huffman::serialize_tree(&tree, &mut bits);
+    bits.extend(huffman::code_original_file(input, &codes));
+
+    let mut out = header(FORMAT_TREE, input.len());
+    out.extend(huffman::pack_bits(&bits));
+    out
+}
+
+//头部只保存每个符号的编码长度，解码方根据长度表重建出相同的规范huffman编码
+pub fn compress_canonical(input: &[u8]) -> Vec<u8> {
+    let freq = huffman::freq_table(input);
+    let tree = huffman::create_huffman_tree(freq);
+    let mut codes = HashMap::new();
+    huffman::generate_huffman_code(Vec::new(), &mut codes, *tree);
+
+    let lengths: Vec<(u8, u16)> = codes
+        .iter()
+        .map(|(ch, code)| (*ch, code.len() as u16))
+        .collect();
+    let canonical_codes = huffman::generate_canonical_codes(&lengths);
+
+    let mut out = header(FORMAT_CANONICAL, input.len());
+    out.extend((lengths.len() as u16).to_le_bytes());
+    for (ch, len) in &lengths {
+        out.push(*ch);
+        out.extend(len.to_le_bytes());
+    }
+    out.extend(huffman::pack_bits(&huffman::code_original_file(
+        input,
+        &canonical_codes,
+    )));
+    out
+}
+
+//一遍扫描完成编码，既不需要频数表也不需要存储树，可用于流式场景
+pub fn compress_adaptive(input: &[u8]) -> Vec<u8> {
+    let mut model = AdaptiveModel::new();
+    let bits: Vec<u8> = input.iter().flat_map(|ch| model.encode(*ch)).collect();
+
+    let mut out = header(FORMAT_ADAPTIVE, input.len());
+    out.extend(huffman::pack_bits(&bits));
+    out
+}
+
+//根据头部的格式标记自动选择解码方式，还原出与压缩前完全一致的字节序列
+pub fn decompress(input: &[u8]) -> Result<Vec<u8>> {
+    if input.len() < 9 {
+        return Err(anyhow!("compressed data is too short to contain a header"));
+    }
+    let format = input[0];
+    let mut len_bytes = [0u8; 8];
+    len_bytes.copy_from_slice(&input[1..9]);
+    let original_len = u64::from_le_bytes(len_bytes) as usize;
+
+    let result = match format {
+        FORMAT_ADAPTIVE => {
+            let mut bits = input[9..].iter().flat_map(|d| huffman::d2b(*d));
+            let mut model = AdaptiveModel::new();
+            let mut result = Vec::with_capacity(original_len);
+            while result.len() < original_len {
+                match model.decode(&mut bits) {
+                    Some(ch) => result.push(ch),
+                    None => {
+                        return Err(anyhow!(
+                            "compressed data is truncated: expected {} decoded bytes, got {}",
+                            original_len,
+                            result.len()
+                        ))
+                    }
+                }
+            }
+            result
+        }
+        FORMAT_CANONICAL => {
+            if input.len() < 11 {
+                return Err(anyhow!("canonical header is truncated"));
+            }
+            let count = u16::from_le_bytes([input[9], input[10]]) as usize;
+            if input.len() < 11 + count * 3 {
+                return Err(anyhow!("canonical code length table is truncated"));
+            }
+            let lengths: Vec<(u8, u16)> = input[11..11 + count * 3]
+                .chunks(3)
+                .map(|entry| (entry[0], u16::from_le_bytes([entry[1], entry[2]])))
+                .collect();
+            let canonical_codes = huffman::generate_canonical_codes(&lengths);
+            let codes = canonical_codes
+                .into_iter()
+                .map(|(ch, code)| (code, ch))
+                .collect();
+
+            let bits: Vec<u8> = input[11 + count * 3..]
+                .iter()
+                .flat_map(|d| huffman::d2b(*d))
+                .collect();
+            huffman::decode(codes, &bits, original_len)?
+        }
+        FORMAT_TREE => {
+            let bits: Vec<u8> = input[9..].iter().flat_map(|d| huffman::d2b(*d)).collect();
+            let mut pos = 0;
+            let tree = huffman::deserialize_tree(&bits, &mut pos)?;
+            let mut codes = HashMap::new();
+            huffman::generate_decode_table(Vec::new(), &mut codes, *tree);
+            huffman::decode(codes, &bits[pos..], original_len)?
+        }
+        other => return Err(anyhow!("unknown compressed format tag: {}", other)),
+    };
+    Ok(result)
+}
+
+fn header(format: u8, original_len: usize) -> Vec<u8> {
+    let mut out = vec![format];
+    out.extend((original_len as u64).to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //自适应huffman的兄弟性质维护若失效，树会退化成链状，压缩率远差于静态huffman甚至膨胀原始数据，
+    //因此用一段64个符号均匀分布的输入验证adaptive至少不比静态huffman差
+    #[test]
+    fn adaptive_compression_is_competitive_with_static() {
+        let input: Vec<u8> = (0..8000u32).map(|i| (i % 64) as u8).collect();
+        let static_len = compress(&input).len();
+        let adaptive_len = compress_adaptive(&input).len();
+        assert!(
+            adaptive_len <= static_len,
+            "adaptive output ({} bytes) should be no larger than static huffman ({} bytes)",
+            adaptive_len,
+            static_len
+        );
+    }
+
+    //覆盖三种格式在边界情况下的完整往返: 空输入、单一符号、含0x00字节、以及256种符号全部出现，
+    //这些恰好是serialize_tree/deserialize_tree、canonical码表反解、以及FGK自适应树最容易出错的路径
+    fn round_trip_cases() -> Vec<Vec<u8>> {
+        vec![
+            Vec::new(),
+            vec![b'x'; 20],
+            vec![0x00, 0x00, 0x00, 1, 0x00, 2, 0x00],
+            (0..=255u8).collect(),
+        ]
+    }
+
+    #[test]
+    fn tree_format_round_trips() {
+        for input in round_trip_cases() {
+            let compressed = compress(&input);
+            assert_eq!(decompress(&compressed).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn canonical_format_round_trips() {
+        for input in round_trip_cases() {
+            let compressed = compress_canonical(&input);
+            assert_eq!(decompress(&compressed).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn adaptive_format_round_trips() {
+        for input in round_trip_cases() {
+            let compressed = compress_adaptive(&input);
+            assert_eq!(decompress(&compressed).unwrap(), input);
+        }
+    }
+
+    //截断过的压缩数据在各格式下都应该返回Err，而不是在deserialize_tree/decode里索引越界panic
+    #[test]
+    fn truncated_input_is_an_error_not_a_panic() {
+        let input = b"hello hello hello world".to_vec();
+        for compressed in [
+            compress(&input),
+            compress_canonical(&input),
+            compress_adaptive(&input),
+        ] {
+            for cut in [9, compressed.len() / 2] {
+                assert!(decompress(&compressed[..cut]).is_err());
+            }
+        }
+    }
+}